@@ -0,0 +1,46 @@
+//! The non-reflected CRC-32 used by the Ogg container format (polynomial `0x04C11DB7`,
+//! initial value `0`, no final XOR). This differs from the common reflected CRC-32.
+
+const POLYNOMIAL: u32 = 0x04C1_1DB7;
+
+fn build_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut crc = (n as u32) << 24;
+        let mut i = 0;
+        while i < 8 {
+            crc = if crc & 0x8000_0000 != 0 { (crc << 1) ^ POLYNOMIAL } else { crc << 1 };
+            i += 1;
+        }
+        table[n] = crc;
+        n += 1;
+    }
+    table
+}
+
+fn table() -> &'static [u32; 256] {
+    static TABLE: std::sync::OnceLock<[u32; 256]> = std::sync::OnceLock::new();
+    TABLE.get_or_init(build_table)
+}
+
+/// Computes the Ogg CRC-32 checksum over `bytes`.
+///
+/// Callers must zero out the 4-byte checksum field within `bytes` before calling this,
+/// since the stored checksum is itself computed with that field set to zero.
+pub(crate) fn checksum(bytes: &[u8]) -> u32 {
+    checksum_chained([bytes])
+}
+
+/// Computes the Ogg CRC-32 checksum over the concatenation of `parts`, without needing them
+/// copied into one contiguous buffer first.
+pub(crate) fn checksum_chained<'a>(parts: impl IntoIterator<Item = &'a [u8]>) -> u32 {
+    let table = table();
+    let mut crc: u32 = 0;
+    for part in parts {
+        for &byte in part {
+            crc = (crc << 8) ^ table[(((crc >> 24) & 0xFF) as u8 ^ byte) as usize];
+        }
+    }
+    crc
+}
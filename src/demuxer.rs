@@ -0,0 +1,161 @@
+use std::collections::VecDeque;
+
+use crate::crc;
+use crate::error::{ParseError, Result};
+use crate::opus_header_structs::{CommentHeader, IdentificationHeader};
+
+/// An incremental, push-based demuxer for Opus-in-Ogg streams.
+///
+/// Unlike the other parsing entry points, a `Demuxer` does not require a seekable or complete
+/// `Read` source: feed it chunks as they arrive (e.g. from a network download) via [`push`],
+/// and pull out the identification header, the comment header, and audio packets as soon as
+/// enough data has arrived to complete them.
+///
+/// [`push`]: Demuxer::push
+#[derive(Debug, Default)]
+pub struct Demuxer {
+    buffer: Vec<u8>,
+    current_packet: Vec<u8>,
+    audio_packets: VecDeque<Vec<u8>>,
+    id: Option<IdentificationHeader>,
+    comments: Option<CommentHeader>,
+    ended: bool,
+}
+
+impl Demuxer {
+    /// Creates an empty demuxer with no data pushed yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds another chunk of the underlying byte stream to the demuxer, parsing as many
+    /// complete Ogg pages out of the accumulated buffer as possible.
+    pub fn push(&mut self, data: &[u8]) -> Result<()> {
+        self.buffer.extend_from_slice(data);
+
+        while let Some(consumed) = self.parse_next_page()? {
+            self.buffer.drain(..consumed);
+        }
+
+        Ok(())
+    }
+
+    /// Attempts to parse a single complete page out of the front of `self.buffer`, starting
+    /// from the next `OggS` capture pattern. Returns the number of leading bytes that should be
+    /// dropped from the buffer (garbage before the page, plus the page itself), or `None` if
+    /// not enough data has arrived yet to complete a page.
+    fn parse_next_page(&mut self) -> Result<Option<usize>> {
+        let start = match self.buffer.windows(4).position(|w| w == b"OggS") {
+            Some(pos) => pos,
+            None => return Ok(None),
+        };
+
+        const FIXED_HEADER_LEN: usize = 27;
+        if self.buffer.len() < start + FIXED_HEADER_LEN {
+            return Ok(None);
+        }
+
+        let header_type = self.buffer[start + 5];
+        let page_segments = self.buffer[start + 26] as usize;
+        let stored_checksum = u32::from_le_bytes(self.buffer[start + 22..start + 26].try_into().unwrap());
+
+        let segment_table_start = start + FIXED_HEADER_LEN;
+        if self.buffer.len() < segment_table_start + page_segments {
+            return Ok(None);
+        }
+        let segment_table = self.buffer[segment_table_start..segment_table_start + page_segments].to_vec();
+
+        let payload_len: usize = segment_table.iter().map(|&b| b as usize).sum();
+        let payload_start = segment_table_start + page_segments;
+        if self.buffer.len() < payload_start + payload_len {
+            return Ok(None);
+        }
+        let payload = self.buffer[payload_start..payload_start + payload_len].to_vec();
+
+        // the stored checksum is computed over the header (with its own checksum field zeroed)
+        // and segment table, followed by the payload; reuse `payload` instead of re-slicing it
+        // out of `self.buffer` a second time.
+        let mut header_and_segment_table = self.buffer[start..payload_start].to_vec();
+        header_and_segment_table[22..26].fill(0);
+        let found_checksum = crc::checksum_chained([&header_and_segment_table[..], &payload[..]]);
+        if found_checksum != stored_checksum {
+            return Err(ParseError::ChecksumMismatch { expected: stored_checksum, found: found_checksum });
+        }
+
+        self.consume_page(&segment_table, &payload)?;
+
+        if header_type & 0x4 != 0 {
+            self.ended = true;
+        }
+
+        Ok(Some(payload_start + payload_len))
+    }
+
+    /// Reassembles packets from a page's segment table and payload. A packet boundary occurs
+    /// after each lacing value less than 255; if the final lacing value of the page is 255, the
+    /// packet continues into the next page instead of completing here.
+    fn consume_page(&mut self, segment_table: &[u8], payload: &[u8]) -> Result<()> {
+        let mut offset = 0;
+        let mut i = 0;
+
+        while i < segment_table.len() {
+            let mut segment_len = 0usize;
+            let mut continues = false;
+            loop {
+                let lacing_value = segment_table[i];
+                segment_len += lacing_value as usize;
+                i += 1;
+                if lacing_value < 255 {
+                    break;
+                }
+                if i >= segment_table.len() {
+                    continues = true;
+                    break;
+                }
+            }
+
+            self.current_packet.extend_from_slice(&payload[offset..offset + segment_len]);
+            offset += segment_len;
+
+            if !continues {
+                self.complete_current_packet()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn complete_current_packet(&mut self) -> Result<()> {
+        let packet = std::mem::take(&mut self.current_packet);
+
+        if self.id.is_none() {
+            self.id = Some(IdentificationHeader::parse(&packet[..])?);
+        } else if self.comments.is_none() {
+            self.comments = Some(CommentHeader::parse(&packet[..], packet.len() as u32)?);
+        } else {
+            self.audio_packets.push_back(packet);
+        }
+
+        Ok(())
+    }
+
+    /// The identification header, once enough data has been pushed to complete it.
+    pub fn id(&self) -> Option<&IdentificationHeader> {
+        self.id.as_ref()
+    }
+
+    /// The comment header, once enough data has been pushed to complete it.
+    pub fn comments(&self) -> Option<&CommentHeader> {
+        self.comments.as_ref()
+    }
+
+    /// Pulls the next completed audio packet, if any has arrived since the last call.
+    pub fn pull_packet(&mut self) -> Option<Vec<u8>> {
+        self.audio_packets.pop_front()
+    }
+
+    /// Whether the underlying Ogg stream has reported its end-of-stream page.
+    pub fn ended(&self) -> bool {
+        self.ended
+    }
+}
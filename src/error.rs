@@ -15,6 +15,8 @@ pub enum ParseError {
     Io(io::Error),
     /// A string decoding error occurred.
     Encoding(str::Utf8Error),
+    /// The underlying `ogg` crate failed to read a page, e.g. because its checksum did not match.
+    Ogg(ogg::OggReadError),
     /// The Ogg page was missing the `OggS` magic.
     InvalidOggPage,
     /// The Opus headers was missing its magic number.
@@ -24,7 +26,20 @@ pub enum ParseError {
     /// Any String within the comment header claims to be larger than the header itself.
     CommentTooLong,
     /// An error occurred while counting the length of the comment header. This is should not happen and should be considered a bug in this librray.
-    LengthMismatch
+    LengthMismatch,
+    /// Re-paginating the Ogg stream while writing produced an inconsistent result. This should not happen and should be considered a bug in this library.
+    WriteFailed,
+    /// The stream ended before both headers could be read.
+    UnexpectedEndOfStream,
+    /// An Ogg page's CRC-32 checksum did not match its recomputed value, indicating a corrupt or
+    /// truncated page. Only returned by [`Demuxer`][crate::Demuxer], which parses pages itself
+    /// rather than through the `ogg` crate's `PacketReader`.
+    ChecksumMismatch { expected: u32, found: u32 },
+    /// A length field requested a buffer too large to allocate. Returned instead of aborting the process.
+    AllocationFailed { requested: usize },
+    /// The channel mapping table is inconsistent, e.g. `coupled_stream_count` exceeds `stream_count`,
+    /// or an entry indexes a decoded channel that does not exist.
+    InvalidChannelMapping
 }
 
 impl From<io::Error> for ParseError {
@@ -39,11 +54,18 @@ impl From<str::Utf8Error> for ParseError {
     }
 }
 
+impl From<ogg::OggReadError> for ParseError {
+    fn from(e: ogg::OggReadError) -> Self {
+        Self::Ogg(e)
+    }
+}
+
 impl error::Error for ParseError {
     fn cause(&self) -> Option<&dyn error::Error> {
         match self {
             ParseError::Io(e) => Some(e),
             ParseError::Encoding(e) => Some(e),
+            ParseError::Ogg(e) => Some(e),
             _ => None
         }
     }
@@ -54,11 +76,17 @@ impl fmt::Display for ParseError {
         match self {
             ParseError::Io(e) => e.fmt(f),
             ParseError::Encoding(e) => e.fmt(f),
+            ParseError::Ogg(e) => e.fmt(f),
             ParseError::InvalidOggPage => f.write_str("missing Ogg page magic"),
             ParseError::InvalidOpusHeader => f.write_str("Opus header is missing the magic signature"),
             ParseError::CommentHeaderTooLarge => f.write_str("Opus comment header is larger than 120MB"),
             ParseError::CommentTooLong => f.write_str("A comment claims to be longer than the Header itself"),
-            ParseError::LengthMismatch => f.write_str("The length of the comment header does not match the calculated length")
+            ParseError::LengthMismatch => f.write_str("The length of the comment header does not match the calculated length"),
+            ParseError::WriteFailed => f.write_str("Failed to re-paginate the Ogg stream while writing"),
+            ParseError::UnexpectedEndOfStream => f.write_str("The stream ended before both headers could be read"),
+            ParseError::ChecksumMismatch { expected, found } => write!(f, "Ogg page checksum mismatch: expected {:#010x}, found {:#010x}", expected, found),
+            ParseError::AllocationFailed { requested } => write!(f, "Failed to allocate {} bytes requested by a length field", requested),
+            ParseError::InvalidChannelMapping => f.write_str("The channel mapping table is inconsistent with its stream counts")
         }
     }
 }
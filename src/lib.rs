@@ -14,6 +14,7 @@ pub use ogg::Packet;
 mod error;
 mod read_ext;
 
+mod crc;
 mod opus_packets;
 
 mod opus_header_structs;
@@ -23,6 +24,15 @@ mod ogg_page;
 use ogg_page::*;
 use opus_packets::OpusPackets;
 
+mod writer;
+pub use writer::write_tags_to_path;
+
+mod demuxer;
+pub use demuxer::Demuxer;
+
+mod pictures;
+pub use pictures::Picture;
+
 #[cfg(test)]
 mod tests;
 
@@ -52,56 +62,63 @@ pub fn parse_from_file(file: &File) -> Result<OpusHeaders> {
 /// Parses an opus file given by a reader.
 /// Either returns the Opus Headers, or an error if anything goes wrong.
 /// This should not panic.
-pub fn parse_from_read<T: Read + Seek>(mut reader: T) -> Result<OpusHeaders> {
+///
+/// This only looks at the first logical Opus bitstream it encounters; use [`parse_all`]
+/// for chained or multiplexed files that may contain more than one.
+pub fn parse_from_read<T: Read + Seek>(reader: T) -> Result<OpusHeaders> {
+    parse_all(reader)?.into_iter().next().ok_or(error::ParseError::UnexpectedEndOfStream)
+}
+
+/// Parses every logical Opus bitstream found in a reader, in the order their identification
+/// header was first seen. This handles chained Opus files (several concatenated streams) as
+/// well as files that multiplex other, non-Opus logical streams alongside Opus: pages are
+/// grouped by their Ogg serial number, and a logical stream is only treated as Opus once its
+/// first packet's payload starts with the `OpusHead` magic.
+/// Either returns the Opus Headers of every Opus stream found, or an error if anything goes wrong.
+/// This should not panic.
+pub fn parse_all<T: Read + Seek>(reader: T) -> Result<Vec<OpusHeaders>> {
+    enum StreamState {
+        AwaitingId,
+        AwaitingComments(IdentificationHeader),
+        Done,
+    }
+
     let mut packet_reader = PacketReader::new(reader);
-    
-    let first_ogg_page = packet_reader.read_packet()?.ok_or(error::ParseError::UnexpectedEndOfStream)?;
 
-    let id = IdentificationHeader::parse(&first_ogg_page.data[..])?;
+    let mut states: std::collections::HashMap<u32, StreamState> = std::collections::HashMap::new();
+    let mut serial_order: Vec<u32> = Vec::new();
+    let mut results: std::collections::HashMap<u32, OpusHeaders> = std::collections::HashMap::new();
 
-    /*
-    let mut comment_pages = vec![];
-    let first_page = OggPage::parse(&mut reader)?;
-    
-    // used to make sure the payload does not exceed 120MB
-    let mut comment_size: u32 = first_page.payload.len() as u32;
-    
-    comment_pages.push(first_page);
-    
-    // header 0x01 signals that the page is the continuation of a previous page
-    loop {
-        let next_page = OggPage::parse(&mut reader)?;
-        if next_page.header_type == 0x01 {
-            comment_size += next_page.payload.len() as u32;
-            if comment_size > MAX_COMMENT_HEADER_LEN {
-                return Err(error::ParseError::CommentHeaderTooLarge); // abort if we exceed the limit
+    while let Some(packet) = packet_reader.read_packet()? {
+        let serial = packet.stream_serial();
+        let data = &packet.data[..];
+
+        let state = states.entry(serial).or_insert_with(|| {
+            serial_order.push(serial);
+            StreamState::AwaitingId
+        });
+
+        match std::mem::replace(state, StreamState::Done) {
+            StreamState::AwaitingId => {
+                *state = if data.starts_with(b"OpusHead") {
+                    StreamState::AwaitingComments(IdentificationHeader::parse(data)?)
+                } else {
+                    // not an Opus logical stream; ignore every further packet on this serial
+                    StreamState::Done
+                };
             }
-            comment_pages.push(next_page);
-        } else {
-            break;
+            StreamState::AwaitingComments(id) => {
+                if data.starts_with(b"OpusTags") {
+                    let comments = CommentHeader::parse(data, data.len() as u32)?;
+                    results.insert(serial, OpusHeaders { id, comments });
+                }
+                // *state is already Done; further packets on this serial are audio data
+            }
+            StreamState::Done => {}
         }
     }
 
-    // the value of comment_len should be equal to comment_size and can thus be MAX_COMMENT_HEADER_LEN at maximum
-    let comment_len = comment_pages.iter().map(|p| p.payload.len()).sum();
-    
-    // sanity check. The only way this can be triggered is if the previous code contains errors
-    if comment_len as u32 != comment_size {
-        return Err(error::ParseError::LengthMismatch);
-    }
-
-    // concatenate all payloads into the actual comment header
-    let mut comment_bytes = Vec::with_capacity(comment_len);
-    for mut page in comment_pages {
-        comment_bytes.append(&mut page.payload);
-    }
-     */
-
-    let comment_packet = packet_reader.read_packet()?.ok_or(error::ParseError::UnexpectedEndOfStream)?;
-
-    let co = CommentHeader::parse(&comment_packet.data[..], comment_packet.data.len() as u32)?;
-
-    Ok(OpusHeaders { id, comments: co })
+    Ok(serial_order.into_iter().filter_map(|serial| results.remove(&serial)).collect())
 }
 
 /// Parses an opus file given by the path.
@@ -121,18 +138,90 @@ pub fn get_opus_payload_from_file(file: &File) -> Result<Vec<Packet>> {
 /// Parses an opus file given by a reader.
 /// Either returns the Opus Packets, or an error if anything goes wrong.
 /// This should not panic.
-pub fn get_opus_payload_from_read<T: Read + Seek>(mut reader: T) -> Result<Vec<Packet>> {
+pub fn get_opus_payload_from_read<T: Read + Seek>(reader: T) -> Result<Vec<Packet>> {
+    packets_from_read(reader)?.read_count(None)
+}
 
+/// Returns a lazy iterator over the audio packets of an opus file given by a reader, after
+/// skipping the identification and comment header packets. Unlike [`get_opus_payload_from_read`],
+/// this does not eagerly read the whole stream into memory, so callers that only need to inspect
+/// the first few packets (or stream-process all of them) can do so in constant memory.
+/// Either returns the iterator, or an error if either header packet is missing.
+pub fn packets_from_read<T: Read + Seek>(reader: T) -> Result<OpusPacketReader<T>> {
     let mut packet_reader = PacketReader::new(reader);
     // parse and ignore the id header packet.
-    let _first_ogg_page = packet_reader.read_packet()?.ok_or(error::ParseError::UnexpectedEndOfStream)?;
+    packet_reader.read_packet()?.ok_or(error::ParseError::UnexpectedEndOfStream)?;
     // parse and ignore the comment header packet
-    let _comment_ogg_page = packet_reader.read_packet()?.ok_or(error::ParseError::UnexpectedEndOfStream)?;
+    packet_reader.read_packet()?.ok_or(error::ParseError::UnexpectedEndOfStream)?;
+
+    Ok(OpusPacketReader { packet_reader })
+}
 
-    let mut opus_packets = vec![];
+/// A lazy, count-bounded iterator over the audio packets of an Opus stream, returned by
+/// [`packets_from_read`].
+pub struct OpusPacketReader<T: Read + Seek> {
+    packet_reader: PacketReader<T>,
+}
+
+impl<T: Read + Seek> OpusPacketReader<T> {
+    /// Reads up to `n` further packets, or to the end of the stream if `n` is `None`.
+    pub fn read_count(&mut self, n: impl Into<Option<usize>>) -> Result<Vec<Packet>> {
+        let limit = n.into();
+        let mut packets = vec![];
+        while limit.map_or(true, |limit| packets.len() < limit) {
+            match self.packet_reader.read_packet()? {
+                Some(packet) => packets.push(packet),
+                None => break,
+            }
+        }
+        Ok(packets)
+    }
+}
+
+impl<T: Read + Seek> Iterator for OpusPacketReader<T> {
+    type Item = Result<Packet>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.packet_reader.read_packet().transpose()
+    }
+}
+
+/// Computes the playback duration of an opus file given by the path, in seconds.
+/// Either returns the duration, or an error if anything goes wrong.
+pub fn duration_from_path<P: AsRef<Path>>(path: P) -> Result<f64> {
+    duration_from_file(&File::open(path)?)
+}
+
+/// Computes the playback duration of an opus file given by the file parameter, in seconds.
+/// Either returns the duration, or an error if anything goes wrong.
+pub fn duration_from_file(file: &File) -> Result<f64> {
+    duration_from_read(BufReader::new(file))
+}
+
+/// Computes the playback duration of an opus file given by a reader, in seconds.
+///
+/// Opus granule positions are always counted in 48 kHz samples, regardless of
+/// `input_sample_rate`, so this reads pages until end-of-stream to find the last granule
+/// position belonging to the Opus logical stream, and derives `(last_granule - pre_skip) / 48000.0`.
+/// Either returns the duration, or an error if anything goes wrong.
+pub fn duration_from_read<T: Read + Seek>(mut reader: T) -> Result<f64> {
+    let first_ogg_page = {
+        let mut packet_reader = PacketReader::new(&mut reader);
+        packet_reader.read_packet()?.ok_or(error::ParseError::UnexpectedEndOfStream)?
+    };
+    let id = IdentificationHeader::parse(&first_ogg_page.data[..])?;
+    let serial = first_ogg_page.stream_serial();
+
+    reader.seek(std::io::SeekFrom::Start(0))?;
+    let mut packet_reader = PacketReader::new(reader);
+
+    let mut last_granule: i64 = 0;
     while let Some(packet) = packet_reader.read_packet()? {
-        opus_packets.push(packet);
+        if packet.stream_serial() == serial {
+            last_granule = packet.absgp_page() as i64;
+        }
     }
 
-    Ok(opus_packets)
+    let samples = (last_granule - id.pre_skip as i64).max(0);
+    Ok(samples as f64 / 48000.0)
 }
@@ -24,11 +24,26 @@ pub struct ChannelMappingTable {
     pub channel_mapping: Vec<u8>,
 }
 
+/// A resolved view of a channel mapping, reporting how many mono and coupled (stereo) streams
+/// an Opus decoder needs to decode, and which decoded channel each output channel maps to.
+/// Returned by [`IdentificationHeader::channel_layout`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelLayout {
+    /// How many streams decode to a single (mono) channel.
+    pub mono_stream_count: u8,
+    /// How many streams decode to a pair of (stereo) channels.
+    pub coupled_stream_count: u8,
+    /// For each output channel position, the decoded channel it is populated from. A value of
+    /// `255` means the output channel is digital silence.
+    pub channel_mapping: Vec<u8>,
+}
+
 /// The Comment header containing a vendor string and the user comments as a map.
+/// A key may be associated with more than one value, as permitted by RFC 7845.
 #[derive(Debug)]
 pub struct CommentHeader {
     pub vendor: String,
-    pub user_comments: HashMap<String, String>,
+    pub user_comments: HashMap<String, Vec<String>>,
 }
 
 impl IdentificationHeader {
@@ -49,7 +64,7 @@ impl IdentificationHeader {
         let channel_mapping_family = reader.read_u8_le()?;
 
         let channel_mapping_table = if channel_mapping_family != 0 {
-            Some(ChannelMappingTable::parse(&mut reader)?)
+            Some(ChannelMappingTable::parse(&mut reader, channel_count)?)
         } else {
             None
         };
@@ -64,16 +79,58 @@ impl IdentificationHeader {
             channel_mapping_table,
         })
     }
+
+    /// The output gain as a decibel multiplier to apply to the decoded samples, per RFC 7845
+    /// section 5.1: `output_gain` is a Q7.8 fixed-point number of decibels.
+    pub fn output_gain_db(&self) -> f32 {
+        self.output_gain as f32 / 256.0
+    }
+
+    /// The output gain as a linear multiplier to apply to the decoded samples.
+    pub fn output_gain_linear(&self) -> f32 {
+        10f32.powf(self.output_gain_db() / 20.0)
+    }
+
+    /// Resolves the channel mapping into a [`ChannelLayout`], the information a multistream
+    /// Opus decoder needs to route decoded channels to output positions. For mapping family 0
+    /// (no channel mapping table), RFC 7845 section 5.1.1 implies a single mono or stereo stream.
+    pub fn channel_layout(&self) -> ChannelLayout {
+        match &self.channel_mapping_table {
+            Some(table) => ChannelLayout {
+                // `coupled_stream_count` is only guaranteed `<= stream_count` for tables that went
+                // through `ChannelMappingTable::parse`; this struct's fields are `pub`, so a
+                // hand-built table could violate that invariant.
+                mono_stream_count: table.stream_count.saturating_sub(table.coupled_stream_count),
+                coupled_stream_count: table.coupled_stream_count,
+                channel_mapping: table.channel_mapping.clone(),
+            },
+            None => ChannelLayout {
+                mono_stream_count: if self.channel_count <= 1 { 1 } else { 0 },
+                coupled_stream_count: if self.channel_count == 2 { 1 } else { 0 },
+                channel_mapping: (0..self.channel_count).collect(),
+            },
+        }
+    }
 }
 
 impl ChannelMappingTable {
     /// parses a channel mapping table.
     /// returns an err if anything goes wrong.
-    pub(crate) fn parse<T: Read>(mut reader: T) -> Result<ChannelMappingTable> {
+    pub(crate) fn parse<T: Read>(mut reader: T, channel_count: u8) -> Result<ChannelMappingTable> {
         let stream_count = reader.read_u8_le()?;
         let coupled_stream_count = reader.read_u8_le()?;
-        // stream count is a u8 -> this allocates 511 Bytes max
-        let channel_mapping = reader.read_byte_vec(stream_count as usize)?;
+
+        if coupled_stream_count > stream_count {
+            return Err(ParseError::InvalidChannelMapping);
+        }
+
+        // per RFC 7845 section 5.1.1, the mapping array has one entry per output channel, not per stream
+        let channel_mapping = reader.read_byte_vec(channel_count as usize)?;
+
+        let stream_total = stream_count as usize + coupled_stream_count as usize;
+        if channel_mapping.iter().any(|&index| index != 255 && index as usize >= stream_total) {
+            return Err(ParseError::InvalidChannelMapping);
+        }
 
         Ok(ChannelMappingTable {
             stream_count,
@@ -122,7 +179,7 @@ impl CommentHeader {
             
             let parts: Vec<_> = commentstr.splitn(2, '=').collect();
             if parts.len() == 2 {
-                comments.insert(parts[0].to_string(), parts[1].to_string());
+                comments.entry(parts[0].to_string()).or_insert_with(Vec::new).push(parts[1].to_string());
             } // else? malformed comment?
         }
 
@@ -131,4 +188,29 @@ impl CommentHeader {
             user_comments: comments,
         })
     }
+
+    /// Returns the first value associated with `key`, if any.
+    pub fn get_value(&self, key: &str) -> Option<&str> {
+        self.user_comments.get(key).and_then(|values| values.first()).map(String::as_str)
+    }
+
+    /// Returns all values associated with `key`, if any.
+    pub fn get_values(&self, key: &str) -> Option<&[String]> {
+        self.user_comments.get(key).map(Vec::as_slice)
+    }
+
+    /// Replaces all values for `key` with a single `value`, discarding any existing ones.
+    pub fn set_value<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
+        self.user_comments.insert(key.into(), vec![value.into()]);
+    }
+
+    /// Adds an additional `value` for `key`, keeping any values already present.
+    pub fn add_value<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
+        self.user_comments.entry(key.into()).or_insert_with(Vec::new).push(value.into());
+    }
+
+    /// Removes all values for `key`, if present.
+    pub fn remove(&mut self, key: &str) {
+        self.user_comments.remove(key);
+    }
 }
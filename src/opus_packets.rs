@@ -16,7 +16,7 @@ impl OpusPacket {
 		let mut relevant_pages = vec![first_page];
 
 		let mut next_packet_page = None;
-		
+
 		while continue_reading {
 			let next_page = OggPage::parse(&mut reader)?;
 
@@ -36,7 +36,7 @@ impl OpusPacket {
 			bytes.append(&mut payload);
 			bytes
 		});
-		
+
 		Ok((Self(bytes), next_packet_page))
 	}
 }
@@ -46,7 +46,7 @@ impl OpusPackets {
 		let mut packets = vec![];
 
 		let mut next_page = first_page;
-		
+
 		loop {
 			let (packet, next) = OpusPacket::parse(&mut reader, next_page)?;
 
@@ -0,0 +1,111 @@
+use crate::opus_header_structs::CommentHeader;
+
+const METADATA_BLOCK_PICTURE_KEY: &str = "METADATA_BLOCK_PICTURE";
+
+/// A picture embedded in a `METADATA_BLOCK_PICTURE` comment, decoded from its FLAC picture block
+/// (see <https://xiph.org/flac/format.html#metadata_block_picture>).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Picture {
+    /// The FLAC picture type, e.g. `3` for "Cover (front)".
+    pub kind: u32,
+    pub mime_type: String,
+    pub description: String,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl CommentHeader {
+    /// Finds every `METADATA_BLOCK_PICTURE` comment (matched case-insensitively), base64-decodes
+    /// its value, and parses the result as a FLAC picture block. Comments that are not valid
+    /// base64 or not a well-formed picture block are silently skipped.
+    pub fn pictures(&self) -> Vec<Picture> {
+        self.user_comments
+            .iter()
+            .filter(|(key, _)| key.eq_ignore_ascii_case(METADATA_BLOCK_PICTURE_KEY))
+            .flat_map(|(_, values)| values.iter())
+            .filter_map(|value| decode_base64(value).and_then(|bytes| Picture::parse(&bytes)))
+            .collect()
+    }
+}
+
+impl Picture {
+    fn parse(bytes: &[u8]) -> Option<Picture> {
+        let mut cursor = Cursor(bytes);
+
+        let kind = cursor.read_u32_be()?;
+
+        let mime_len = cursor.read_u32_be()? as usize;
+        let mime_type = String::from_utf8(cursor.read_bytes(mime_len)?.to_vec()).ok()?;
+
+        let description_len = cursor.read_u32_be()? as usize;
+        let description = String::from_utf8(cursor.read_bytes(description_len)?.to_vec()).ok()?;
+
+        let width = cursor.read_u32_be()?;
+        let height = cursor.read_u32_be()?;
+        let _color_depth = cursor.read_u32_be()?;
+        let _indexed_color_count = cursor.read_u32_be()?;
+
+        let data_len = cursor.read_u32_be()? as usize;
+        let data = cursor.read_bytes(data_len)?.to_vec();
+
+        Some(Picture { kind, mime_type, description, width, height, data })
+    }
+}
+
+/// A minimal cursor over a byte slice, used only to parse the fixed big-endian FLAC picture layout.
+struct Cursor<'a>(&'a [u8]);
+
+impl<'a> Cursor<'a> {
+    fn read_bytes(&mut self, amount: usize) -> Option<&'a [u8]> {
+        if self.0.len() < amount {
+            return None;
+        }
+        let (taken, rest) = self.0.split_at(amount);
+        self.0 = rest;
+        Some(taken)
+    }
+
+    fn read_u32_be(&mut self) -> Option<u32> {
+        let bytes = self.read_bytes(4)?;
+        Some(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Decodes a standard (RFC 4648), optionally padded, base64 string.
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut table = [None; 256];
+    for (value, &symbol) in BASE64_ALPHABET.iter().enumerate() {
+        table[symbol as usize] = Some(value as u32);
+    }
+
+    let cleaned: Vec<u8> = input.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+    let cleaned = cleaned.strip_suffix(b"==").or_else(|| cleaned.strip_suffix(b"=")).unwrap_or(&cleaned);
+
+    let mut out = Vec::with_capacity(cleaned.len() * 3 / 4);
+    for chunk in cleaned.chunks(4) {
+        // a trailing chunk of 1 char cannot encode a byte under RFC 4648; reject it instead of
+        // silently decoding from zero-padded garbage.
+        if chunk.len() < 2 {
+            return None;
+        }
+
+        let mut buf = [0u32; 4];
+        for (i, &byte) in chunk.iter().enumerate() {
+            buf[i] = table[byte as usize]?;
+        }
+
+        let combined = (buf[0] << 18) | (buf[1] << 12) | (buf[2] << 6) | buf[3];
+        out.push((combined >> 16) as u8);
+        if chunk.len() > 2 {
+            out.push((combined >> 8) as u8);
+        }
+        if chunk.len() > 3 {
+            out.push(combined as u8);
+        }
+    }
+
+    Some(out)
+}
@@ -1,5 +1,7 @@
 use std::io::{Read, Result};
 
+use crate::error::{ParseError, Result as CrateResult};
+
 pub trait ReadExt {
     fn read_u8_le(&mut self) -> Result<u8>;
     fn read_i8_le(&mut self) -> Result<i8>;
@@ -9,7 +11,9 @@ pub trait ReadExt {
     fn read_i32_le(&mut self) -> Result<i32>;
     fn read_u64_le(&mut self) -> Result<u64>;
     fn read_i64_le(&mut self) -> Result<i64>;
-    fn read_byte_vec(&mut self, amount: usize) -> Result<Vec<u8>>;
+    /// Allocates `amount` bytes and fills them from the reader. Uses fallible allocation so a
+    /// hostile or corrupt length field cannot abort the process; it yields `ParseError::AllocationFailed` instead.
+    fn read_byte_vec(&mut self, amount: usize) -> CrateResult<Vec<u8>>;
     fn read_four_bytes(&mut self) -> Result<[u8; 4]>;
     fn read_eight_bytes(&mut self) -> Result<[u8; 8]>;
 }
@@ -63,11 +67,25 @@ impl<T> ReadExt for T where T: Read {
         Ok(i64::from_le_bytes(buf))
     }
 
-    // note that this function allocates the given amount of Bytes before actually reading anything.
-    // Thus, one needs to be careful to not exhaust the computers memory by passing a very large 'amount' parameter
-    fn read_byte_vec(&mut self, amount: usize) -> Result<Vec<u8>> {
-        let mut buf = vec![0; amount];
-        self.read_exact(&mut buf)?;
+    // Reads in bounded chunks instead of reserving the whole (possibly hostile) 'amount' up
+    // front, so a bogus length field cannot drive a multi-gigabyte reservation before the bytes
+    // backing it have actually arrived. Each chunk still uses fallible allocation, turning an
+    // allocation failure into an error instead of aborting the process.
+    fn read_byte_vec(&mut self, amount: usize) -> CrateResult<Vec<u8>> {
+        const CHUNK_SIZE: usize = 64 * 1024;
+
+        let mut buf = Vec::new();
+        let mut remaining = amount;
+        while remaining > 0 {
+            let chunk_len = remaining.min(CHUNK_SIZE);
+            buf.try_reserve_exact(chunk_len).map_err(|_| ParseError::AllocationFailed { requested: amount })?;
+
+            let start = buf.len();
+            buf.resize(start + chunk_len, 0);
+            self.read_exact(&mut buf[start..])?;
+
+            remaining -= chunk_len;
+        }
         Ok(buf)
     }
     
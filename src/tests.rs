@@ -1,7 +1,13 @@
+use std::fs::File;
 use std::path::Path;
 
 use crate::error::ParseError;
-use crate::parse_from_path;
+use crate::crc;
+use crate::read_ext::ReadExt;
+use crate::{
+    duration_from_path, get_opus_payload_from_file, packets_from_read, parse_all, parse_from_path, parse_from_read,
+    ChannelMappingTable, CommentHeader, Demuxer, IdentificationHeader,
+};
 
 #[test]
 fn test_standard_file() {
@@ -56,6 +62,250 @@ fn test_large_file() {
     assert_eq!(comments.get_value("LYRICS").unwrap().len(), 210_000);
 }
 
+#[test]
+fn test_write_to_round_trip() {
+    let path = Path::new("test/silence_standard.opus");
+    let mut headers = parse_from_path(path).unwrap();
+    headers.comments.set_value("TITLE", "a new title");
+
+    let mut rewritten = Vec::new();
+    headers.write_to(File::open(path).unwrap(), &mut rewritten).unwrap();
+
+    let reparsed = parse_from_read(std::io::Cursor::new(rewritten)).unwrap();
+    assert_eq!(reparsed.comments.get_value("TITLE").unwrap(), "a new title");
+    // unrelated tags should survive the rewrite untouched
+    assert_eq!(reparsed.comments.get_value("ARTIST").unwrap(), "artist_tag");
+}
+
+#[test]
+fn test_duration() {
+    let path = Path::new("test/silence_standard.opus");
+    let duration = duration_from_path(path).unwrap();
+    assert!(duration > 0.0, "expected a positive duration, got {}", duration);
+}
+
+#[test]
+fn test_channel_layout_mapping_family_0() {
+    let mono = IdentificationHeader {
+        version: 1,
+        channel_count: 1,
+        pre_skip: 0,
+        input_sample_rate: 48000,
+        output_gain: 0,
+        channel_mapping_family: 0,
+        channel_mapping_table: None,
+    };
+    let layout = mono.channel_layout();
+    assert_eq!(layout.mono_stream_count, 1);
+    assert_eq!(layout.coupled_stream_count, 0);
+    assert_eq!(layout.channel_mapping, vec![0]);
+
+    let stereo = IdentificationHeader {
+        channel_count: 2,
+        ..mono
+    };
+    let layout = stereo.channel_layout();
+    assert_eq!(layout.mono_stream_count, 0);
+    assert_eq!(layout.coupled_stream_count, 1);
+    assert_eq!(layout.channel_mapping, vec![0, 1]);
+}
+
+#[test]
+fn test_channel_layout_explicit_mapping_table() {
+    let header = IdentificationHeader {
+        version: 1,
+        channel_count: 4,
+        pre_skip: 0,
+        input_sample_rate: 48000,
+        output_gain: 0,
+        channel_mapping_family: 1,
+        channel_mapping_table: Some(ChannelMappingTable {
+            stream_count: 3,
+            coupled_stream_count: 1,
+            channel_mapping: vec![0, 1, 2, 3],
+        }),
+    };
+
+    let layout = header.channel_layout();
+    assert_eq!(layout.mono_stream_count, 2);
+    assert_eq!(layout.coupled_stream_count, 1);
+    assert_eq!(layout.channel_mapping, vec![0, 1, 2, 3]);
+}
+
+#[test]
+fn test_parse_all_finds_the_single_opus_stream() {
+    let path = Path::new("test/silence_standard.opus");
+    let headers = parse_all(File::open(path).unwrap()).unwrap();
+
+    assert_eq!(headers.len(), 1);
+    assert_eq!(headers[0].comments.get_value("TITLE").unwrap(), "tag_title");
+}
+
+#[test]
+fn test_packets_from_read_is_bounded_and_lazy() {
+    let path = Path::new("test/silence_standard.opus");
+    let all_packets = get_opus_payload_from_file(&File::open(path).unwrap()).unwrap();
+
+    let mut reader = packets_from_read(File::open(path).unwrap()).unwrap();
+    let first_two = reader.read_count(2).unwrap();
+    assert_eq!(first_two.len(), 2);
+    assert_eq!(first_two[0].data, all_packets[0].data);
+    assert_eq!(first_two[1].data, all_packets[1].data);
+
+    // the rest of the stream is still available afterwards, confirming read_count didn't
+    // drain more than it was asked for
+    let rest = reader.read_count(None).unwrap();
+    assert_eq!(rest.len(), all_packets.len() - 2);
+}
+
+#[test]
+fn test_read_byte_vec_spans_multiple_chunks() {
+    // bigger than the internal 64KB chunk size, to exercise the chunked read loop
+    let expected: Vec<u8> = (0..200_000u32).map(|i| (i % 256) as u8).collect();
+    let mut cursor = std::io::Cursor::new(expected.clone());
+
+    let read = cursor.read_byte_vec(expected.len()).unwrap();
+    assert_eq!(read, expected);
+}
+
+#[test]
+fn test_pictures_decodes_embedded_flac_picture_block() {
+    let mime = "image/png";
+    let description = "cover";
+    let data = [0xDEu8, 0xAD, 0xBE, 0xEF];
+
+    let mut block = Vec::new();
+    block.extend_from_slice(&3u32.to_be_bytes()); // kind: "Cover (front)"
+    block.extend_from_slice(&(mime.len() as u32).to_be_bytes());
+    block.extend_from_slice(mime.as_bytes());
+    block.extend_from_slice(&(description.len() as u32).to_be_bytes());
+    block.extend_from_slice(description.as_bytes());
+    block.extend_from_slice(&16u32.to_be_bytes()); // width
+    block.extend_from_slice(&16u32.to_be_bytes()); // height
+    block.extend_from_slice(&8u32.to_be_bytes()); // color depth
+    block.extend_from_slice(&0u32.to_be_bytes()); // indexed color count
+    block.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    block.extend_from_slice(&data);
+
+    let mut comments = CommentHeader { vendor: String::new(), user_comments: Default::default() };
+    comments.add_value("METADATA_BLOCK_PICTURE", encode_base64(&block));
+
+    let pictures = comments.pictures();
+    assert_eq!(pictures.len(), 1);
+    assert_eq!(pictures[0].kind, 3);
+    assert_eq!(pictures[0].mime_type, mime);
+    assert_eq!(pictures[0].description, description);
+    assert_eq!(pictures[0].width, 16);
+    assert_eq!(pictures[0].height, 16);
+    assert_eq!(pictures[0].data, data);
+}
+
+/// A standard (RFC 4648), padded base64 encoder, used only to build test fixtures for the
+/// decoder in `pictures.rs`.
+fn encode_base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::new();
+
+    for chunk in input.chunks(3) {
+        let mut buf = [0u8; 3];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let combined = (buf[0] as u32) << 16 | (buf[1] as u32) << 8 | buf[2] as u32;
+
+        out.push(ALPHABET[(combined >> 18 & 0x3F) as usize] as char);
+        out.push(ALPHABET[(combined >> 12 & 0x3F) as usize] as char);
+        out.push(if chunk.len() > 1 { ALPHABET[(combined >> 6 & 0x3F) as usize] as char } else { '=' });
+        out.push(if chunk.len() > 2 { ALPHABET[(combined & 0x3F) as usize] as char } else { '=' });
+    }
+
+    out
+}
+
+#[test]
+fn test_demuxer_reassembles_headers_and_audio_incrementally() {
+    // OpusHead: magic + version + channel_count + pre_skip + input_sample_rate + output_gain
+    // + channel_mapping_family(0, so no mapping table follows)
+    let mut id_payload = Vec::new();
+    id_payload.extend_from_slice(b"OpusHead");
+    id_payload.push(1); // version
+    id_payload.push(2); // channel_count
+    id_payload.extend_from_slice(&0u16.to_le_bytes()); // pre_skip
+    id_payload.extend_from_slice(&48000u32.to_le_bytes()); // input_sample_rate
+    id_payload.extend_from_slice(&0i16.to_le_bytes()); // output_gain
+    id_payload.push(0); // channel_mapping_family
+
+    // OpusTags: magic + empty vendor string + zero comments
+    let mut tags_payload = Vec::new();
+    tags_payload.extend_from_slice(b"OpusTags");
+    tags_payload.extend_from_slice(&0u32.to_le_bytes());
+    tags_payload.extend_from_slice(&0u32.to_le_bytes());
+
+    let audio_payload = b"fake audio frame".to_vec();
+
+    let serial = 0x1234_5678;
+    let mut stream = Vec::new();
+    stream.extend_from_slice(&build_ogg_page(&id_payload, serial, 0, 0, false));
+    stream.extend_from_slice(&build_ogg_page(&tags_payload, serial, 1, 0, false));
+    stream.extend_from_slice(&build_ogg_page(&audio_payload, serial, 2, 312, true));
+
+    let mut demuxer = Demuxer::new();
+
+    // feed it byte-by-byte-ish, in small chunks, to exercise the incremental buffering
+    for chunk in stream.chunks(7) {
+        demuxer.push(chunk).unwrap();
+    }
+
+    let id = demuxer.id().unwrap();
+    assert_eq!(id.channel_count, 2);
+
+    let comments = demuxer.comments().unwrap();
+    assert_eq!(comments.vendor, "");
+
+    assert_eq!(demuxer.pull_packet().unwrap(), audio_payload);
+    assert!(demuxer.ended());
+}
+
+#[test]
+fn test_demuxer_rejects_a_page_with_a_corrupted_checksum() {
+    let mut id_payload = Vec::new();
+    id_payload.extend_from_slice(b"OpusHead");
+    id_payload.push(1);
+    id_payload.push(1);
+    id_payload.extend_from_slice(&0u16.to_le_bytes());
+    id_payload.extend_from_slice(&48000u32.to_le_bytes());
+    id_payload.extend_from_slice(&0i16.to_le_bytes());
+    id_payload.push(0);
+
+    let mut page = build_ogg_page(&id_payload, 1, 0, 0, false);
+    let last = page.len() - 1;
+    page[last] ^= 0xFF; // flip the bits of the last payload byte without touching the stored checksum
+
+    let mut demuxer = Demuxer::new();
+    let err = demuxer.push(&page).unwrap_err();
+    assert!(matches!(err, ParseError::ChecksumMismatch { .. }));
+}
+
+/// Builds a single, correctly-CRC'd Ogg page around `payload`, for feeding to [`Demuxer::push`]
+/// in tests without needing a real file on disk.
+fn build_ogg_page(payload: &[u8], serial: u32, sequence: u32, granule: i64, last_page: bool) -> Vec<u8> {
+    assert!(payload.len() < 255, "test helper only supports single-segment pages");
+
+    let mut page = Vec::new();
+    page.extend_from_slice(b"OggS");
+    page.push(0); // version
+    page.push(if last_page { 0x4 } else { 0x0 }); // header_type
+    page.extend_from_slice(&granule.to_le_bytes());
+    page.extend_from_slice(&serial.to_le_bytes());
+    page.extend_from_slice(&sequence.to_le_bytes());
+    page.extend_from_slice(&0u32.to_le_bytes()); // checksum placeholder, filled in below
+    page.push(1); // page_segments
+    page.push(payload.len() as u8); // segment_table
+    page.extend_from_slice(payload);
+
+    let checksum = crc::checksum(&page);
+    page[22..26].copy_from_slice(&checksum.to_le_bytes());
+    page
+}
+
 #[test]
 fn test_malformed_file_1() {
     let path = Path::new("test/silence_malformed_missing_magic.opus");
@@ -0,0 +1,112 @@
+use std::fs::File;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+
+use ogg::{PacketReader, PacketWriter, PacketWriteEndInfo};
+
+use crate::error::{ParseError, Result};
+use crate::opus_header_structs::CommentHeader;
+use crate::{parse_from_path, OpusHeaders};
+
+impl CommentHeader {
+    /// Serializes this comment header back into the `OpusTags` byte layout:
+    /// magic, length-prefixed vendor string, comment count, then each
+    /// `KEY=VALUE` pair length-prefixed as a little-endian `u32`.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(b"OpusTags");
+
+        bytes.extend_from_slice(&(self.vendor.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.vendor.as_bytes());
+
+        let comment_count: u32 = self.user_comments.values().map(|v| v.len() as u32).sum();
+        bytes.extend_from_slice(&comment_count.to_le_bytes());
+
+        for (key, values) in &self.user_comments {
+            for value in values {
+                let entry = format!("{}={}", key, value);
+                bytes.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+                bytes.extend_from_slice(entry.as_bytes());
+            }
+        }
+
+        bytes
+    }
+}
+
+impl OpusHeaders {
+    /// Writes the identification header, this (possibly edited) comment header, and all
+    /// audio packets read from `reader` to `writer`, producing a valid Opus/Ogg stream.
+    /// The identification header and the audio packets are copied through byte-for-byte;
+    /// only the comment packet is re-encoded. Every page, including the audio pages, is
+    /// rebuilt by `PacketWriter` (with freshly assigned sequence numbers and checksums)
+    /// rather than copied verbatim.
+    /// Either returns `Ok(())`, or an error if anything goes wrong.
+    pub fn write_to<R: Read + Seek, W: Write>(&self, reader: R, writer: W) -> Result<()> {
+        let mut packet_reader = PacketReader::new(reader);
+
+        let id_packet = packet_reader.read_packet()?.ok_or(ParseError::UnexpectedEndOfStream)?;
+        let serial = id_packet.stream_serial();
+
+        // consume and discard the original comment packet, it is replaced below
+        packet_reader.read_packet()?.ok_or(ParseError::UnexpectedEndOfStream)?;
+
+        let mut packet_writer = PacketWriter::new(writer);
+
+        packet_writer.write_packet(id_packet.data, serial, PacketWriteEndInfo::EndPage, id_packet.absgp_page())?;
+
+        let comment_bytes = self.comments.to_bytes();
+        packet_writer.write_packet(comment_bytes, serial, PacketWriteEndInfo::EndPage, 0)?;
+
+        while let Some(packet) = packet_reader.read_packet()? {
+            let end_info = if packet.last_in_stream() {
+                PacketWriteEndInfo::EndStream
+            } else if packet.last_in_page() {
+                PacketWriteEndInfo::EndPage
+            } else {
+                PacketWriteEndInfo::NormalPacket
+            };
+
+            packet_writer.write_packet(packet.data, packet.stream_serial(), end_info, packet.absgp_page())?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads `file`, rewrites it with this (possibly edited) comment header, and persists the
+    /// result back to `file`. Uses a buffered two-pass approach, since the source and the
+    /// destination are the same file: everything is read into memory first, then written back.
+    /// Either returns `Ok(())`, or an error if anything goes wrong.
+    pub fn write_to_file(&self, mut file: &File) -> Result<()> {
+        let mut source = Vec::new();
+        file.seek(std::io::SeekFrom::Start(0))?;
+        file.read_to_end(&mut source)?;
+
+        let mut rewritten = Vec::new();
+        self.write_to(std::io::Cursor::new(source), &mut rewritten)?;
+
+        file.seek(std::io::SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        file.write_all(&rewritten)?;
+
+        Ok(())
+    }
+
+    /// Reads the file at `path`, rewrites it with this (possibly edited) comment header, and
+    /// persists the result back to that path.
+    /// Either returns `Ok(())`, or an error if anything goes wrong.
+    pub fn write_to_path<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let file = File::options().read(true).write(true).open(path)?;
+        self.write_to_file(&file)
+    }
+}
+
+/// Rewrites just the comment header of the Opus file at `path` to `comments`, preserving the
+/// identification header and all audio packets byte-for-byte. This is a convenience
+/// wrapper around parsing the existing identification header and calling
+/// [`OpusHeaders::write_to_path`] with it alongside the new comments.
+/// Either returns `Ok(())`, or an error if anything goes wrong.
+pub fn write_tags_to_path<P: AsRef<Path>>(path: P, comments: CommentHeader) -> Result<()> {
+    let id = parse_from_path(&path)?.id;
+    OpusHeaders { id, comments }.write_to_path(path)
+}